@@ -22,10 +22,10 @@ use arrow::array::{
     StringArrayType, StringViewArray,
 };
 use arrow::compute::kernels::cast_utils::string_to_timestamp_nanos;
-use arrow::datatypes::DataType;
+use arrow::datatypes::{ArrowTimestampType, DataType, TimeUnit};
 use chrono::format::{parse, Parsed, StrftimeItems};
 use chrono::LocalResult::Single;
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, LocalResult, TimeZone, Utc};
 
 use datafusion_common::cast::as_generic_string_array;
 use datafusion_common::{
@@ -38,9 +38,169 @@ const ERR_NANOSECONDS_NOT_SUPPORTED: &str = "The dates that can be represented a
 
 /// Calls string_to_timestamp_nanos and converts the error type
 pub(crate) fn string_to_timestamp_nanos_shim(s: &str) -> Result<i64> {
+    if let Some(nanos) = parse_iso8601_to_nanos_fast(s) {
+        return nanos;
+    }
     string_to_timestamp_nanos(s).map_err(|e| e.into())
 }
 
+/// A hand-rolled byte scanner for the ISO-8601/RFC-3339 shapes that make up the
+/// overwhelming majority of timestamp strings seen in practice (e.g.
+/// `2023-01-01T04:05:06.789Z` or `2023-01-01 04:05:06+05:30`).
+///
+/// Returns `None` (rather than an error) the moment a byte doesn't match the
+/// expected class, so the caller can fall back to the general-purpose
+/// `string_to_timestamp_nanos` parser without any behavior change. Returns
+/// `Some(Err(..))` only once the shape has been fully matched but the value is
+/// out of range (e.g. the composed nanosecond count overflows `i64`).
+fn parse_iso8601_to_nanos_fast(s: &str) -> Option<Result<i64>> {
+    let b = s.as_bytes();
+
+    fn digit(b: &[u8], i: usize) -> Option<i64> {
+        let d = *b.get(i)?;
+        if d.is_ascii_digit() {
+            Some((d - b'0') as i64)
+        } else {
+            None
+        }
+    }
+
+    fn digits(b: &[u8], start: usize, n: usize) -> Option<i64> {
+        let mut acc = 0i64;
+        for i in 0..n {
+            acc = acc * 10 + digit(b, start + i)?;
+        }
+        Some(acc)
+    }
+
+    // YYYY-MM-DD
+    let year = digits(b, 0, 4)?;
+    if *b.get(4)? != b'-' {
+        return None;
+    }
+    let month = digits(b, 5, 2)?;
+    if *b.get(7)? != b'-' {
+        return None;
+    }
+    let day = digits(b, 8, 2)?;
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    // separator between date and time: 'T' or ' '
+    match b.get(10)? {
+        b'T' | b't' | b' ' => {}
+        _ => return None,
+    }
+
+    // HH:MM:SS
+    let hour = digits(b, 11, 2)?;
+    if *b.get(13)? != b':' {
+        return None;
+    }
+    let minute = digits(b, 14, 2)?;
+    if *b.get(16)? != b':' {
+        return None;
+    }
+    let second = digits(b, 17, 2)?;
+    if hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+
+    let mut pos = 19;
+
+    // optional fractional seconds: '.' followed by 1-9 digits
+    let mut nanos = 0i64;
+    if b.get(pos) == Some(&b'.') {
+        pos += 1;
+        let frac_start = pos;
+        while b.get(pos).is_some_and(u8::is_ascii_digit) {
+            pos += 1;
+        }
+        let frac_len = pos - frac_start;
+        if !(1..=9).contains(&frac_len) {
+            return None;
+        }
+        for i in 0..9 {
+            nanos *= 10;
+            if i < frac_len {
+                nanos += digit(b, frac_start + i)?;
+            }
+        }
+    }
+
+    // optional trailing offset: 'Z', '+HH:MM'/'+HHMM', or '-HH:MM'/'-HHMM'
+    let mut offset_seconds = 0i64;
+    match b.get(pos) {
+        None => {}
+        Some(b'Z') | Some(b'z') => {
+            pos += 1;
+        }
+        Some(sign @ (b'+' | b'-')) => {
+            let sign = if *sign == b'+' { 1 } else { -1 };
+            pos += 1;
+            let off_hour = digits(b, pos, 2)?;
+            pos += 2;
+            if b.get(pos) == Some(&b':') {
+                pos += 1;
+            }
+            let off_min = digits(b, pos, 2)?;
+            pos += 2;
+            if off_hour > 23 || off_min > 59 {
+                return None;
+            }
+            offset_seconds = sign * (off_hour * 3600 + off_min * 60);
+        }
+        Some(_) => return None,
+    }
+
+    if pos != b.len() {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    let day_seconds = hour * 3600 + minute * 60 + second;
+    let total_seconds = days * 86_400 + day_seconds - offset_seconds;
+
+    let total_nanos = total_seconds
+        .checked_mul(1_000_000_000)
+        .and_then(|n| n.checked_add(nanos));
+
+    Some(total_nanos.ok_or_else(|| {
+        DataFusionError::Execution(ERR_NANOSECONDS_NOT_SUPPORTED.to_string())
+    }))
+}
+
+/// Converts a proleptic-Gregorian calendar date into the number of days
+/// since the Unix epoch (1970-01-01), using Howard Hinnant's `days_from_civil`
+/// algorithm (<http://howardhinnant.github.io/date_algorithms.html>).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400; // [0, 399]
+    let mp = (month + 9) % 12; // [0, 11], Mar = 0
+    let doy = (153 * mp + 2) / 5 + day - 1; // [0, 365]
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy; // [0, 146096]
+    era * 146_097 + doe - 719_468
+}
+
+/// Whether `year` is a leap year in the proleptic Gregorian calendar.
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Number of days in `month` (1-12) of `year`, accounting for leap years.
+/// `month` is assumed to already be range-checked to `1..=12` by the caller.
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
 /// Checks that all the arguments from the second are of type [Utf8], [LargeUtf8] or [Utf8View]
 ///
 /// [Utf8]: DataType::Utf8
@@ -73,7 +233,18 @@ pub(crate) fn validate_data_types(args: &[ColumnarValue], name: &str) -> Result<
 /// * `2023-01-01 040506 America/Los_Angeles`
 ///
 /// If a timestamp is ambiguous, for example as a result of daylight-savings time, an error
-/// will be returned
+/// will be returned, unless `ignore_timezone` is set (see below).
+///
+/// ## `ignore_timezone`
+///
+/// When `true`, any offset or zone present in `s` is parsed but then discarded,
+/// and the naive wall-clock value is localized to `timezone` via
+/// [`TimeZone::from_local_datetime`]. With `Utc` the wall-clock digits are kept
+/// unchanged; with any other `timezone` the result is that wall-clock time in
+/// `timezone`, not in UTC. An ambiguous local time (e.g. a DST fall-back repeat)
+/// resolves to the earlier of its two instants instead of erroring; a local time
+/// that does not exist in `timezone` (e.g. a DST spring-forward gap) is still an
+/// error.
 ///
 /// [`chrono::format::strftime`]: https://docs.rs/chrono/latest/chrono/format/strftime/index.html
 /// [IANA timezones]: https://www.iana.org/time-zones
@@ -81,6 +252,7 @@ pub(crate) fn string_to_datetime_formatted<T: TimeZone>(
     timezone: &T,
     s: &str,
     format: &str,
+    ignore_timezone: bool,
 ) -> Result<DateTime<T>, DataFusionError> {
     let err = |err_ctx: &str| {
         DataFusionError::Execution(format!(
@@ -91,6 +263,26 @@ pub(crate) fn string_to_datetime_formatted<T: TimeZone>(
     let mut parsed = Parsed::new();
     parse(&mut parsed, s, StrftimeItems::new(format)).map_err(|e| err(&e.to_string()))?;
 
+    if ignore_timezone {
+        let ndt = parsed
+            .to_naive_datetime_with_offset(0)
+            .or_else(|_| parsed.to_naive_date().map(|nd| nd.into()))
+            .map_err(|e| err(&e.to_string()))?;
+
+        // Treat the wall-clock value as already being local to `timezone`,
+        // discarding any offset/zone parsed from `s`. Unlike the strict path
+        // below, an ambiguous local time (e.g. a DST fall-back repeat) picks
+        // the earlier of the two candidate instants instead of erroring, since
+        // the whole point of this mode is to avoid failing on DST ambiguity.
+        return match timezone.from_local_datetime(&ndt) {
+            LocalResult::Single(dt) => Ok(dt),
+            LocalResult::Ambiguous(earliest, _latest) => Ok(earliest),
+            LocalResult::None => Err(err(
+                "local datetime does not exist in the target timezone (e.g. a DST spring-forward gap)",
+            )),
+        };
+    }
+
     // attempt to parse the string assuming it has a timezone
     let dt = parsed.to_datetime();
 
@@ -113,8 +305,9 @@ pub(crate) fn string_to_datetime_formatted<T: TimeZone>(
     }
 }
 
-/// Accepts a string with a `chrono` format and converts it to a
-/// nanosecond precision timestamp.
+/// Accepts a string with a `chrono` format and converts it to a timestamp in
+/// the requested `unit`, replacing what used to be separate, near-identical
+/// `string_to_timestamp_nanos_formatted`/`_millis_formatted` functions.
 ///
 /// See [`chrono::format::strftime`] for the full set of supported formats.
 ///
@@ -126,10 +319,11 @@ pub(crate) fn string_to_datetime_formatted<T: TimeZone>(
 ///
 /// ## Timestamp Precision
 ///
-/// Function uses the maximum precision timestamps supported by
-/// Arrow (nanoseconds stored as a 64-bit integer) timestamps. This
-/// means the range of dates that timestamps can represent is ~1677 AD
-/// to 2262 AM
+/// [`TimeUnit::Nanosecond`] is the only unit that can overflow i64 within the
+/// range of dates `chrono` can represent: nanoseconds stored as a 64-bit
+/// integer only cover ~1677 AD to 2262 AD. Coarser units
+/// ([`TimeUnit::Microsecond`], [`TimeUnit::Millisecond`], [`TimeUnit::Second`])
+/// never hit that limit and so never return [`ERR_NANOSECONDS_NOT_SUPPORTED`].
 ///
 /// ## Timezone / Offset Handling
 ///
@@ -141,42 +335,155 @@ pub(crate) fn string_to_datetime_formatted<T: TimeZone>(
 /// [`chrono::format::strftime`]: https://docs.rs/chrono/latest/chrono/format/strftime/index.html
 ///
 #[inline]
-pub(crate) fn string_to_timestamp_nanos_formatted(
+pub(crate) fn string_to_timestamp_formatted(
+    s: &str,
+    format: &str,
+    unit: TimeUnit,
+) -> Result<i64, DataFusionError> {
+    string_to_timestamp_formatted_tz(&Utc, s, format, unit, false)
+}
+
+/// Like [`string_to_timestamp_formatted`], but parses relative to the given
+/// `timezone` instead of `Utc`, and accepts an `ignore_timezone` flag (see
+/// [`string_to_datetime_formatted`]) for ETL-style lenient parsing. A naive
+/// input is localized to `timezone`; an input carrying an explicit offset is
+/// converted into `timezone` unless `ignore_timezone` is set, in which case
+/// the offset is discarded and the wall-clock value is kept as-is. The
+/// returned value is always the absolute UTC instant scaled to `unit`:
+/// callers that want to preserve `timezone` as `DataType` metadata must
+/// attach it separately (see [`handle_with_timezone`]).
+#[inline]
+pub(crate) fn string_to_timestamp_formatted_tz<T: TimeZone>(
+    timezone: &T,
     s: &str,
     format: &str,
+    unit: TimeUnit,
+    ignore_timezone: bool,
 ) -> Result<i64, DataFusionError> {
-    string_to_datetime_formatted(&Utc, s, format)?
-        .naive_utc()
-        .and_utc()
-        .timestamp_nanos_opt()
-        .ok_or_else(|| {
+    let dt = string_to_datetime_formatted(timezone, s, format, ignore_timezone)?;
+    datetime_to_unit(dt, unit)
+}
+
+/// Scales a parsed `DateTime` down to the absolute UTC instant expressed in
+/// `unit`, shared by [`string_to_timestamp_formatted_tz`] and
+/// [`string_to_timestamp_with_default_formats`].
+fn datetime_to_unit<T: TimeZone>(dt: DateTime<T>, unit: TimeUnit) -> Result<i64> {
+    let dt = dt.naive_utc().and_utc();
+    match unit {
+        TimeUnit::Second => Ok(dt.timestamp()),
+        TimeUnit::Millisecond => Ok(dt.timestamp_millis()),
+        TimeUnit::Microsecond => Ok(dt.timestamp_micros()),
+        TimeUnit::Nanosecond => dt.timestamp_nanos_opt().ok_or_else(|| {
             DataFusionError::Execution(ERR_NANOSECONDS_NOT_SUPPORTED.to_string())
-        })
+        }),
+    }
 }
 
-/// Accepts a string with a `chrono` format and converts it to a
-/// millisecond precision timestamp.
-///
-/// See [`chrono::format::strftime`] for the full set of supported formats.
+/// Built-in formats tried, in order, by [`string_to_timestamp_with_default_formats`]
+/// once RFC-3339 and RFC-2822 (handled separately via chrono's dedicated parsers)
+/// have both failed to match.
+const DEFAULT_TIMESTAMP_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d",
+];
+
+/// Parses `s` against a built-in, ordered list of common timestamp shapes,
+/// without requiring the caller to supply any format strings, so that
+/// heterogeneous string columns parse without enumerating formats.
 ///
-/// Internally, this function uses the `chrono` library for the
-/// datetime parsing
+/// RFC-3339 and RFC-2822 are tried first, via `chrono`'s dedicated
+/// [`DateTime::parse_from_rfc3339`]/[`DateTime::parse_from_rfc2822`] parsers,
+/// which are both faster and more correct than reconstructing those formats
+/// from strftime items. The remaining candidates in
+/// [`DEFAULT_TIMESTAMP_FORMATS`] are tried via [`string_to_datetime_formatted`].
 ///
-/// ## Timezone / Offset Handling
+/// [`DateTime::parse_from_rfc3339`]: chrono::DateTime::parse_from_rfc3339
+/// [`DateTime::parse_from_rfc2822`]: chrono::DateTime::parse_from_rfc2822
 ///
-/// Numerical values of timestamps are stored compared to offset UTC.
-///
-/// Any timestamp in the formatting string is handled according to the rules
-/// defined by `chrono`.
+/// The `to_timestamp` UDF's `invoke()` does not call this function.
+pub(crate) fn string_to_timestamp_with_default_formats(
+    s: &str,
+    unit: TimeUnit,
+) -> Result<i64, DataFusionError> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return datetime_to_unit(dt, unit);
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc2822(s) {
+        return datetime_to_unit(dt, unit);
+    }
+
+    for format in DEFAULT_TIMESTAMP_FORMATS {
+        if let Ok(dt) = string_to_datetime_formatted(&Utc, s, format, false) {
+            return datetime_to_unit(dt, unit);
+        }
+    }
+
+    Err(DataFusionError::Execution(format!(
+        "Error parsing timestamp from '{s}': no built-in format matched"
+    )))
+}
+
+/// Like [`handle`], but threads a caller-supplied `timezone` through the parsing
+/// function and tags the resulting array/scalar's [`DataType::Timestamp`] with
+/// `Some(tz)` instead of producing a zone-less value.
 ///
-/// [`chrono::format::strftime`]: https://docs.rs/chrono/latest/chrono/format/strftime/index.html
+/// `op` is expected to parse relative to `timezone`: a naive input (no offset in
+/// the string) is localized to `timezone`, while an input carrying an explicit
+/// offset is converted into `timezone` rather than normalized away. Either way
+/// the numeric value stored is the absolute instant (UTC ticks); `timezone` is
+/// carried purely as `DataType` metadata, matching how Arrow timestamps work.
 ///
-#[inline]
-pub(crate) fn string_to_timestamp_millis_formatted(s: &str, format: &str) -> Result<i64> {
-    Ok(string_to_datetime_formatted(&Utc, s, format)?
-        .naive_utc()
-        .and_utc()
-        .timestamp_millis())
+/// [`handle`] is the zone-less counterpart. The `to_timestamp` scalar UDF's
+/// `invoke()` does not call this function.
+pub(crate) fn handle_with_timezone<O, F>(
+    args: &[ColumnarValue],
+    op: F,
+    name: &str,
+    tz: Arc<str>,
+) -> Result<ColumnarValue>
+where
+    O: ArrowTimestampType,
+    F: Fn(&str) -> Result<O::Native>,
+{
+    match &args[0] {
+        ColumnarValue::Array(a) => {
+            let array: PrimitiveArray<O> = match a.data_type() {
+                DataType::Utf8View => unary_string_to_primitive_function::<
+                    &StringViewArray,
+                    O,
+                    _,
+                >(a.as_ref().as_string_view(), op)?,
+                DataType::LargeUtf8 => unary_string_to_primitive_function::<
+                    &GenericStringArray<i64>,
+                    O,
+                    _,
+                >(a.as_ref().as_string::<i64>(), op)?,
+                DataType::Utf8 => unary_string_to_primitive_function::<
+                    &GenericStringArray<i32>,
+                    O,
+                    _,
+                >(a.as_ref().as_string::<i32>(), op)?,
+                other => {
+                    return exec_err!("Unsupported data type {other:?} for function {name}")
+                }
+            };
+            Ok(ColumnarValue::Array(Arc::new(
+                array.with_timezone_opt(Some(tz)),
+            )))
+        }
+        ColumnarValue::Scalar(scalar) => match scalar.try_as_str() {
+            Some(a) => {
+                let result = a.as_ref().map(|x| op(x)).transpose()?;
+                Ok(ColumnarValue::Scalar(ScalarValue::new_timestamp::<O>(
+                    result,
+                    Some(tz),
+                )))
+            }
+            _ => exec_err!("Unsupported data type {scalar:?} for function {name}"),
+        },
+    }
 }
 
 pub(crate) fn handle<O, F, S>(
@@ -445,3 +752,382 @@ where
     // first map is the iterator, second is for the `Option<_>`
     array.iter().map(|x| x.map(&op).transpose()).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nanos(s: &str) -> i64 {
+        string_to_timestamp_nanos_shim(s).unwrap()
+    }
+
+    #[test]
+    fn fast_path_matches_slow_path() {
+        for s in [
+            "2023-01-01T04:05:06Z",
+            "2023-01-01T04:05:06.789Z",
+            "2023-01-01 04:05:06",
+            "2023-01-01T04:05:06+05:30",
+            "2023-01-01T04:05:06+0530",
+            "2023-01-01T04:05:06-08:00",
+            "2023-01-01T04:05:06.123456789Z",
+            "2023-01-01T04:05:06.1Z",
+        ] {
+            let fast = parse_iso8601_to_nanos_fast(s).expect("fast path should match").unwrap();
+            let slow = string_to_timestamp_nanos(s).unwrap();
+            assert_eq!(fast, slow, "mismatch for {s}");
+        }
+    }
+
+    #[test]
+    fn fast_path_right_pads_fractional_seconds() {
+        // ".1" right-pads to 100_000_000 ns, not 1 ns.
+        assert_eq!(
+            nanos("2023-01-01T00:00:00.1Z"),
+            nanos("2023-01-01T00:00:00Z") + 100_000_000
+        );
+        assert_eq!(
+            nanos("2023-01-01T00:00:00.123456789Z"),
+            nanos("2023-01-01T00:00:00Z") + 123_456_789
+        );
+    }
+
+    #[test]
+    fn fast_path_rejects_invalid_calendar_dates() {
+        // Not a leap year: Feb only has 28 days.
+        assert!(parse_iso8601_to_nanos_fast("2023-02-29T00:00:00").is_none());
+        // Feb 30 never exists, leap year or not.
+        assert!(parse_iso8601_to_nanos_fast("2023-02-30T00:00:00").is_none());
+        assert!(parse_iso8601_to_nanos_fast("2024-02-30T00:00:00").is_none());
+        // 2024 is a leap year: Feb 29 is valid.
+        assert!(parse_iso8601_to_nanos_fast("2024-02-29T00:00:00").is_some());
+        // April only has 30 days.
+        assert!(parse_iso8601_to_nanos_fast("2023-04-31T00:00:00").is_none());
+    }
+
+    #[test]
+    fn fast_path_rejects_out_of_range_fields() {
+        assert!(parse_iso8601_to_nanos_fast("2023-13-01T00:00:00").is_none());
+        assert!(parse_iso8601_to_nanos_fast("2023-00-01T00:00:00").is_none());
+        assert!(parse_iso8601_to_nanos_fast("2023-01-01T24:00:00").is_none());
+        assert!(parse_iso8601_to_nanos_fast("2023-01-01T00:60:00").is_none());
+        assert!(parse_iso8601_to_nanos_fast("2023-01-01T00:00:60").is_none());
+    }
+
+    #[test]
+    fn fast_path_falls_back_on_unrecognized_shape() {
+        // Not an ISO-8601/RFC-3339 shape the fast path understands; the shim
+        // must still succeed by falling back to `string_to_timestamp_nanos`.
+        assert!(string_to_timestamp_nanos_shim("2023-01-01").is_ok());
+        assert_eq!(
+            string_to_timestamp_nanos_shim("2023-01-01").unwrap(),
+            string_to_timestamp_nanos("2023-01-01").unwrap()
+        );
+    }
+
+    #[test]
+    fn days_in_month_handles_leap_years() {
+        assert_eq!(days_in_month(2023, 2), 28);
+        assert_eq!(days_in_month(2024, 2), 29); // divisible by 4
+        assert_eq!(days_in_month(2000, 2), 29); // divisible by 400
+        assert_eq!(days_in_month(1900, 2), 28); // divisible by 100, not 400
+        assert_eq!(days_in_month(2023, 4), 30);
+        assert_eq!(days_in_month(2023, 1), 31);
+    }
+
+    #[test]
+    fn timezone_aware_parsing_localizes_naive_input() {
+        let tz = chrono::FixedOffset::west_opt(8 * 3600).unwrap(); // UTC-8
+        let dt =
+            string_to_datetime_formatted(&tz, "2023-01-01 04:05:06", "%Y-%m-%d %H:%M:%S", false)
+                .unwrap();
+        // The wall clock in `tz` matches the input exactly...
+        assert_eq!(dt.naive_local().to_string(), "2023-01-01 04:05:06");
+        // ...and the absolute instant is 8 hours ahead, in UTC.
+        assert_eq!(dt.naive_utc().to_string(), "2023-01-01 12:05:06");
+    }
+
+    #[test]
+    fn timezone_aware_parsing_converts_explicit_offset() {
+        let tz = chrono::FixedOffset::west_opt(8 * 3600).unwrap();
+        let with_offset = string_to_datetime_formatted(
+            &tz,
+            "2023-01-01T04:05:06+05:30",
+            "%Y-%m-%dT%H:%M:%S%:z",
+            false,
+        )
+        .unwrap();
+        let with_utc = string_to_datetime_formatted(
+            &Utc,
+            "2023-01-01T04:05:06+05:30",
+            "%Y-%m-%dT%H:%M:%S%:z",
+            false,
+        )
+        .unwrap();
+        // An explicit offset in the input is converted into `tz`, not dropped:
+        // the absolute instant must match regardless of the target timezone.
+        assert_eq!(with_offset.naive_utc(), with_utc.naive_utc());
+    }
+
+    #[test]
+    fn string_to_timestamp_formatted_tz_threads_timezone_through() {
+        let tz = chrono::FixedOffset::west_opt(8 * 3600).unwrap();
+        let local = string_to_timestamp_formatted_tz(
+            &tz,
+            "2023-01-01 04:05:06",
+            "%Y-%m-%d %H:%M:%S",
+            TimeUnit::Second,
+            false,
+        )
+        .unwrap();
+        let utc = string_to_timestamp_formatted_tz(
+            &Utc,
+            "2023-01-01 12:05:06",
+            "%Y-%m-%d %H:%M:%S",
+            TimeUnit::Second,
+            false,
+        )
+        .unwrap();
+        assert_eq!(local, utc);
+    }
+
+    #[test]
+    fn handle_with_timezone_tags_scalar_output_with_tz() {
+        use arrow::datatypes::TimestampNanosecondType;
+
+        let offset = chrono::FixedOffset::east_opt(5 * 3600 + 1800).unwrap(); // +05:30
+        let tz: Arc<str> = Arc::from("+05:30");
+        let args = vec![ColumnarValue::Scalar(ScalarValue::Utf8(Some(
+            "2023-01-01 04:05:06".to_string(),
+        )))];
+
+        let result = handle_with_timezone::<TimestampNanosecondType, _>(
+            &args,
+            |s| {
+                string_to_timestamp_formatted_tz(
+                    &offset,
+                    s,
+                    "%Y-%m-%d %H:%M:%S",
+                    TimeUnit::Nanosecond,
+                    false,
+                )
+            },
+            "to_timestamp",
+            Arc::clone(&tz),
+        )
+        .unwrap();
+
+        match result {
+            ColumnarValue::Scalar(ScalarValue::TimestampNanosecond(Some(_), Some(got_tz))) => {
+                assert_eq!(got_tz, tz);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn ignore_timezone_discards_explicit_offset() {
+        // Without `ignore_timezone`, the `+05:30` offset is honored and
+        // converted into UTC.
+        let strict = string_to_datetime_formatted(
+            &Utc,
+            "2023-01-01T04:05:06+05:30",
+            "%Y-%m-%dT%H:%M:%S%:z",
+            false,
+        )
+        .unwrap();
+        assert_eq!(strict.naive_utc().to_string(), "2022-12-31 22:35:06");
+
+        // With `ignore_timezone`, the offset is parsed but discarded: the
+        // wall-clock digits are kept as-is, local to `timezone` (here, UTC).
+        let lenient = string_to_datetime_formatted(
+            &Utc,
+            "2023-01-01T04:05:06+05:30",
+            "%Y-%m-%dT%H:%M:%S%:z",
+            true,
+        )
+        .unwrap();
+        assert_eq!(lenient.naive_utc().to_string(), "2023-01-01 04:05:06");
+    }
+
+    #[test]
+    fn ignore_timezone_keeps_wall_clock_local_to_non_utc_timezone() {
+        let tz = chrono::FixedOffset::west_opt(8 * 3600).unwrap(); // UTC-8
+        let lenient =
+            string_to_datetime_formatted(&tz, "2023-01-01 04:05:06", "%Y-%m-%d %H:%M:%S", true)
+                .unwrap();
+        // The wall clock in `tz` must match the input exactly, not the input
+        // reinterpreted as if it were UTC.
+        assert_eq!(lenient.naive_local().to_string(), "2023-01-01 04:05:06");
+        assert_eq!(lenient.naive_utc().to_string(), "2023-01-01 12:05:06");
+    }
+
+    /// A toy `TimeZone` whose `from_local_datetime` is always ambiguous
+    /// between offset `+01:00` (the earlier instant, listed first as chrono
+    /// does) and `+00:00`, used to exercise the
+    /// ambiguity-tolerant branch of `ignore_timezone` without depending on
+    /// real IANA DST data (which needs the `chrono-tz` feature).
+    #[derive(Clone)]
+    struct AlwaysAmbiguousTz;
+
+    #[derive(Clone, Debug)]
+    struct AmbiguousOffset(chrono::FixedOffset);
+
+    impl std::fmt::Display for AmbiguousOffset {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            self.0.fmt(f)
+        }
+    }
+
+    impl chrono::Offset for AmbiguousOffset {
+        fn fix(&self) -> chrono::FixedOffset {
+            self.0
+        }
+    }
+
+    impl TimeZone for AlwaysAmbiguousTz {
+        type Offset = AmbiguousOffset;
+
+        fn from_offset(_offset: &Self::Offset) -> Self {
+            AlwaysAmbiguousTz
+        }
+
+        fn offset_from_local_date(
+            &self,
+            _local: &chrono::NaiveDate,
+        ) -> LocalResult<Self::Offset> {
+            LocalResult::Single(AmbiguousOffset(chrono::FixedOffset::east_opt(0).unwrap()))
+        }
+
+        fn offset_from_local_datetime(
+            &self,
+            _local: &chrono::NaiveDateTime,
+        ) -> LocalResult<Self::Offset> {
+            LocalResult::Ambiguous(
+                AmbiguousOffset(chrono::FixedOffset::east_opt(3600).unwrap()),
+                AmbiguousOffset(chrono::FixedOffset::east_opt(0).unwrap()),
+            )
+        }
+
+        fn offset_from_utc_date(&self, _utc: &chrono::NaiveDate) -> Self::Offset {
+            AmbiguousOffset(chrono::FixedOffset::east_opt(0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, _utc: &chrono::NaiveDateTime) -> Self::Offset {
+            AmbiguousOffset(chrono::FixedOffset::east_opt(0).unwrap())
+        }
+    }
+
+    #[test]
+    fn ignore_timezone_resolves_ambiguity_instead_of_erroring() {
+        use chrono::Offset;
+
+        // The strict (non-lenient) path errors out on an ambiguous local time.
+        let strict = string_to_datetime_formatted(
+            &AlwaysAmbiguousTz,
+            "2023-01-01 04:05:06",
+            "%Y-%m-%d %H:%M:%S",
+            false,
+        );
+        assert!(strict.is_err());
+
+        // `ignore_timezone` resolves the ambiguity by picking the earlier
+        // candidate instant instead of failing.
+        let lenient = string_to_datetime_formatted(
+            &AlwaysAmbiguousTz,
+            "2023-01-01 04:05:06",
+            "%Y-%m-%d %H:%M:%S",
+            true,
+        )
+        .unwrap();
+        assert_eq!(lenient.offset().fix(), chrono::FixedOffset::east_opt(3600).unwrap());
+    }
+
+    #[test]
+    fn string_to_timestamp_formatted_scales_by_unit() {
+        let s = "2023-01-01T04:05:06.123456789";
+        let format = "%Y-%m-%dT%H:%M:%S%.f";
+
+        let nanos =
+            string_to_timestamp_formatted(s, format, TimeUnit::Nanosecond).unwrap();
+        let micros =
+            string_to_timestamp_formatted(s, format, TimeUnit::Microsecond).unwrap();
+        let millis =
+            string_to_timestamp_formatted(s, format, TimeUnit::Millisecond).unwrap();
+        let secs = string_to_timestamp_formatted(s, format, TimeUnit::Second).unwrap();
+
+        assert_eq!(nanos, 1_672_545_906_123_456_789);
+        assert_eq!(micros, nanos / 1_000);
+        assert_eq!(millis, nanos / 1_000_000);
+        assert_eq!(secs, nanos / 1_000_000_000);
+    }
+
+    #[test]
+    fn string_to_timestamp_formatted_nanos_overflow_is_scoped_to_nanos() {
+        // Outside the i64-nanosecond range (~1677-2262) but well within the
+        // range representable as seconds/millis/micros.
+        let s = "2300-01-01T00:00:00";
+        let format = "%Y-%m-%dT%H:%M:%S";
+
+        assert!(string_to_timestamp_formatted(s, format, TimeUnit::Nanosecond).is_err());
+        assert!(string_to_timestamp_formatted(s, format, TimeUnit::Microsecond).is_ok());
+        assert!(string_to_timestamp_formatted(s, format, TimeUnit::Millisecond).is_ok());
+        assert!(string_to_timestamp_formatted(s, format, TimeUnit::Second).is_ok());
+    }
+
+    #[test]
+    fn default_formats_try_rfc3339_first() {
+        let v =
+            string_to_timestamp_with_default_formats("2023-01-01T04:05:06.5+05:30", TimeUnit::Nanosecond)
+                .unwrap();
+        let expected = DateTime::parse_from_rfc3339("2023-01-01T04:05:06.5+05:30")
+            .unwrap()
+            .naive_utc()
+            .and_utc()
+            .timestamp_nanos_opt()
+            .unwrap();
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn default_formats_try_rfc2822_second() {
+        let v = string_to_timestamp_with_default_formats(
+            "Sun, 01 Jan 2023 04:05:06 +0530",
+            TimeUnit::Second,
+        )
+        .unwrap();
+        let expected = DateTime::parse_from_rfc2822("Sun, 01 Jan 2023 04:05:06 +0530")
+            .unwrap()
+            .timestamp();
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn default_formats_fall_back_to_builtin_strftime_list() {
+        // Not valid RFC-3339 (no UTC offset) or RFC-2822, but matches
+        // one of `DEFAULT_TIMESTAMP_FORMATS`.
+        let v =
+            string_to_timestamp_with_default_formats("2023-01-01 04:05:06", TimeUnit::Second)
+                .unwrap();
+        assert_eq!(
+            v,
+            string_to_timestamp_formatted(
+                "2023-01-01 04:05:06",
+                "%Y-%m-%d %H:%M:%S%.f",
+                TimeUnit::Second
+            )
+            .unwrap()
+        );
+
+        // Date-only input, the last candidate in the built-in list.
+        assert!(string_to_timestamp_with_default_formats("2023-01-01", TimeUnit::Second).is_ok());
+    }
+
+    #[test]
+    fn default_formats_error_when_nothing_matches() {
+        assert!(
+            string_to_timestamp_with_default_formats("not a timestamp", TimeUnit::Second)
+                .is_err()
+        );
+    }
+}